@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::fmt;
 
 #[derive(Clone, PartialEq, Debug)]
@@ -5,6 +6,52 @@ pub enum Term {
   Var(String),
   Abs(String, Box<Term>),
   App(Box<Term>, Box<Term>),
+  // The type of types.
+  Typ,
+  // Dependent function type `(name : domain) -> codomain`, where `name` is
+  // bound in `codomain`.
+  Pi {
+    name: String,
+    domain: Box<Term>,
+    codomain: Box<Term>,
+  },
+  // A type annotation `term : typ`, used to seed bidirectional checking.
+  Ann(Box<Term>, Box<Term>),
+  // A data constructor applied to its arguments, e.g. `S (S Z)`.
+  Ctr { name: String, args: Vec<Term> },
+  // A call to a user-defined, pattern-matched function, e.g. `Add a b`.
+  Fun { name: String, args: Vec<Term> },
+  // A native machine number, e.g. the `2` in `1 + 1`.
+  Lit(u64),
+  // A primitive arithmetic operator applied to its two operands.
+  Prim { op: Op, left: Box<Term>, right: Box<Term> },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Op {
+  Add,
+  Sub,
+  Mul,
+}
+
+impl Op {
+  fn as_str(&self) -> &'static str {
+    match self {
+      Op::Add => "+",
+      Op::Sub => "-",
+      Op::Mul => "*",
+    }
+  }
+
+  // Standard arithmetic precedence, used to decide when `pretty_print` needs
+  // to parenthesize a nested `Prim` to round-trip correctly (`Mul` binds
+  // tighter than `Add`/`Sub`).
+  fn precedence(&self) -> u8 {
+    match self {
+      Op::Add | Op::Sub => 1,
+      Op::Mul => 2,
+    }
+  }
 }
 
 impl Term {
@@ -23,6 +70,49 @@ impl Term {
         };
         format!("{} {}", func_str, arg_str)
       }
+      Term::Typ => "Type".to_string(),
+      Term::Pi { name, domain, codomain } => {
+        if name == "_" {
+          format!("{} -> {}", domain.pretty_print(), codomain.pretty_print())
+        } else {
+          format!("({}: {}) -> {}", name, domain.pretty_print(), codomain.pretty_print())
+        }
+      }
+      Term::Ann(term, typ) => format!("{} : {}", term.pretty_print(), typ.pretty_print()),
+      Term::Ctr { name, args } | Term::Fun { name, args } => {
+        if args.is_empty() {
+          name.clone()
+        } else {
+          let args_str = args
+            .iter()
+            .map(|arg| match arg {
+              Term::Ctr { args, .. } | Term::Fun { args, .. } if !args.is_empty() => {
+                format!("({})", arg.pretty_print())
+              }
+              Term::App(_, _) | Term::Abs(_, _) => format!("({})", arg.pretty_print()),
+              _ => arg.pretty_print(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+          format!("{} {}", name, args_str)
+        }
+      }
+      Term::Lit(n) => n.to_string(),
+      Term::Prim { op, left, right } => {
+        // Operators are left-associative, so a right operand of equal
+        // precedence needs parens too (`1 - (2 - 3)`).
+        let operand = |term: &Term, is_right: bool| match term {
+          Term::Prim { op: inner, .. }
+            if inner.precedence() < op.precedence()
+              || (is_right && inner.precedence() == op.precedence()) =>
+          {
+            format!("({})", term.pretty_print())
+          }
+          Term::Abs(_, _) => format!("({})", term.pretty_print()),
+          _ => term.pretty_print(),
+        };
+        format!("{} {} {}", operand(left, false), op.as_str(), operand(right, true))
+      }
     }
   }
 
@@ -53,18 +143,115 @@ impl Term {
         tree.push_str(&func.ascii_tree_helper(&format!("{}│ ", indent), false));
         tree.push_str(&arg.ascii_tree_helper(&format!("{}  ", indent), true));
       }
+      Term::Typ => {
+        tree.push_str("Typ\n");
+      }
+      Term::Pi { name, domain, codomain } => {
+        tree.push_str(&format!("Pi ({})\n", name));
+        tree.push_str(&domain.ascii_tree_helper(&format!("{}│ ", indent), false));
+        tree.push_str(&codomain.ascii_tree_helper(&format!("{}  ", indent), true));
+      }
+      Term::Ann(term, typ) => {
+        tree.push_str("Ann\n");
+        tree.push_str(&term.ascii_tree_helper(&format!("{}│ ", indent), false));
+        tree.push_str(&typ.ascii_tree_helper(&format!("{}  ", indent), true));
+      }
+      Term::Ctr { name, args } => {
+        tree.push_str(&format!("Ctr ({})\n", name));
+        push_args_tree(&mut tree, args, indent);
+      }
+      Term::Fun { name, args } => {
+        tree.push_str(&format!("Fun ({})\n", name));
+        push_args_tree(&mut tree, args, indent);
+      }
+      Term::Lit(n) => {
+        tree.push_str(&format!("Lit ({})\n", n));
+      }
+      Term::Prim { op, left, right } => {
+        tree.push_str(&format!("Prim ({})\n", op.as_str()));
+        tree.push_str(&left.ascii_tree_helper(&format!("{}│ ", indent), false));
+        tree.push_str(&right.ascii_tree_helper(&format!("{}  ", indent), true));
+      }
     }
 
     tree
   }
 }
 
+fn push_args_tree(tree: &mut String, args: &[Term], indent: &str) {
+  for (i, arg) in args.iter().enumerate() {
+    let is_last = i == args.len() - 1;
+    let child_indent = if is_last {
+      format!("{}  ", indent)
+    } else {
+      format!("{}│ ", indent)
+    };
+    tree.push_str(&arg.ascii_tree_helper(&child_indent, is_last));
+  }
+}
+
 impl fmt::Display for Term {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     write!(f, "{}", self.pretty_print())
   }
 }
 
+pub fn free_vars(term: &Term) -> HashSet<String> {
+  match term {
+    Term::Var(x) => {
+      let mut vars = HashSet::new();
+      vars.insert(x.clone());
+      vars
+    }
+    Term::Abs(param, body) => {
+      let mut vars = free_vars(body);
+      vars.remove(param);
+      vars
+    }
+    Term::App(t1, t2) => {
+      let mut vars = free_vars(t1);
+      vars.extend(free_vars(t2));
+      vars
+    }
+    Term::Typ => HashSet::new(),
+    Term::Pi { name, domain, codomain } => {
+      let mut vars = free_vars(domain);
+      let mut codomain_vars = free_vars(codomain);
+      codomain_vars.remove(name);
+      vars.extend(codomain_vars);
+      vars
+    }
+    Term::Ann(term, typ) => {
+      let mut vars = free_vars(term);
+      vars.extend(free_vars(typ));
+      vars
+    }
+    Term::Ctr { args, .. } | Term::Fun { args, .. } => {
+      let mut vars = HashSet::new();
+      for arg in args {
+        vars.extend(free_vars(arg));
+      }
+      vars
+    }
+    Term::Lit(_) => HashSet::new(),
+    Term::Prim { left, right, .. } => {
+      let mut vars = free_vars(left);
+      vars.extend(free_vars(right));
+      vars
+    }
+  }
+}
+
+// Produces a name that does not occur in `avoid`, by appending primes to
+// `base` until the clash disappears (`x`, `x'`, `x''`, ...).
+pub(crate) fn fresh_name(base: &str, avoid: &HashSet<String>) -> String {
+  let mut name = base.to_string();
+  while avoid.contains(&name) {
+    name.push('\'');
+  }
+  name
+}
+
 pub fn subst(var: &str, replacement: &Term, term: &Term) -> Term {
   match term {
     Term::Var(x) => {
@@ -77,6 +264,13 @@ pub fn subst(var: &str, replacement: &Term, term: &Term) -> Term {
     Term::Abs(param, body) => {
       if param == var {
         Term::Abs(param.clone(), body.clone())
+      } else if free_vars(replacement).contains(param) {
+        let mut avoid = free_vars(body);
+        avoid.extend(free_vars(replacement));
+        avoid.insert(var.to_string());
+        let renamed_param = fresh_name(param, &avoid);
+        let renamed_body = subst(param, &Term::Var(renamed_param.clone()), body);
+        Term::Abs(renamed_param, Box::new(subst(var, replacement, &renamed_body)))
       } else {
         Term::Abs(param.clone(), Box::new(subst(var, replacement, body)))
       }
@@ -85,6 +279,48 @@ pub fn subst(var: &str, replacement: &Term, term: &Term) -> Term {
       Box::new(subst(var, replacement, t1)),
       Box::new(subst(var, replacement, t2)),
     ),
+    Term::Typ => Term::Typ,
+    Term::Pi { name, domain, codomain } => {
+      let domain = Box::new(subst(var, replacement, domain));
+      if name == var {
+        Term::Pi { name: name.clone(), domain, codomain: codomain.clone() }
+      } else if free_vars(replacement).contains(name) {
+        let mut avoid = free_vars(codomain);
+        avoid.extend(free_vars(replacement));
+        avoid.insert(var.to_string());
+        let renamed_name = fresh_name(name, &avoid);
+        let renamed_codomain = subst(name, &Term::Var(renamed_name.clone()), codomain);
+        Term::Pi {
+          name: renamed_name,
+          domain,
+          codomain: Box::new(subst(var, replacement, &renamed_codomain)),
+        }
+      } else {
+        Term::Pi {
+          name: name.clone(),
+          domain,
+          codomain: Box::new(subst(var, replacement, codomain)),
+        }
+      }
+    }
+    Term::Ann(term, typ) => Term::Ann(
+      Box::new(subst(var, replacement, term)),
+      Box::new(subst(var, replacement, typ)),
+    ),
+    Term::Ctr { name, args } => Term::Ctr {
+      name: name.clone(),
+      args: args.iter().map(|arg| subst(var, replacement, arg)).collect(),
+    },
+    Term::Fun { name, args } => Term::Fun {
+      name: name.clone(),
+      args: args.iter().map(|arg| subst(var, replacement, arg)).collect(),
+    },
+    Term::Lit(n) => Term::Lit(*n),
+    Term::Prim { op, left, right } => Term::Prim {
+      op: *op,
+      left: Box::new(subst(var, replacement, left)),
+      right: Box::new(subst(var, replacement, right)),
+    },
   }
 }
 
@@ -132,4 +368,75 @@ mod tests {
       Term::App(Box::new(var_y.clone()), Box::new(var_y))
     );
   }
+
+  #[test]
+  fn test_pi_pretty_print() {
+    let non_dependent = Term::Pi {
+      name: "_".to_string(),
+      domain: Box::new(Term::Var("Nat".to_string())),
+      codomain: Box::new(Term::Var("Nat".to_string())),
+    };
+    assert_eq!(non_dependent.pretty_print(), "Nat -> Nat");
+
+    let dependent = Term::Pi {
+      name: "a".to_string(),
+      domain: Box::new(Term::Typ),
+      codomain: Box::new(Term::Var("a".to_string())),
+    };
+    assert_eq!(dependent.pretty_print(), "(a: Type) -> a");
+  }
+
+  #[test]
+  fn test_ctr_pretty_print() {
+    let zero = Term::Ctr { name: "Z".to_string(), args: vec![] };
+    assert_eq!(zero.pretty_print(), "Z");
+
+    let two = Term::Ctr {
+      name: "S".to_string(),
+      args: vec![Term::Ctr { name: "S".to_string(), args: vec![zero.clone()] }],
+    };
+    assert_eq!(two.pretty_print(), "S (S Z)");
+  }
+
+  #[test]
+  fn test_prim_pretty_print() {
+    let sum = Term::Prim {
+      op: Op::Add,
+      left: Box::new(Term::Lit(1)),
+      right: Box::new(Term::Lit(2)),
+    };
+    assert_eq!(sum.pretty_print(), "1 + 2");
+
+    // `*` binds tighter than `+`, so `1 + 2 * 3` needs no parens, but the
+    // reverse association does.
+    let mul = Term::Prim {
+      op: Op::Mul,
+      left: Box::new(Term::Lit(2)),
+      right: Box::new(Term::Lit(3)),
+    };
+    let sum_of_mul = Term::Prim { op: Op::Add, left: Box::new(Term::Lit(1)), right: Box::new(mul) };
+    assert_eq!(sum_of_mul.pretty_print(), "1 + 2 * 3");
+
+    let mul_of_sum = Term::Prim {
+      op: Op::Mul,
+      left: Box::new(sum),
+      right: Box::new(Term::Lit(3)),
+    };
+    assert_eq!(mul_of_sum.pretty_print(), "(1 + 2) * 3");
+  }
+
+  #[test]
+  fn test_capture_avoiding_substitution() {
+    // Substituting [x := y] into \y. x must not capture the free `y`:
+    // the result should be \y'. y, not \y. y.
+    let replacement = Term::Var("y".to_string());
+    let term = Term::Abs("y".to_string(), Box::new(Term::Var("x".to_string())));
+
+    let result = subst("x", &replacement, &term);
+
+    assert_eq!(
+      result,
+      Term::Abs("y'".to_string(), Box::new(Term::Var("y".to_string())))
+    );
+  }
 }
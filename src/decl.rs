@@ -0,0 +1,45 @@
+use crate::ast::Term;
+
+// A pattern matched against a constructed value on the left-hand side of a
+// function rule, e.g. the `b` and `S b` in `Add a (S b) = ...`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Pattern {
+  Var(String),
+  Ctr(String, Vec<Pattern>),
+}
+
+// One equation of a pattern-matched function, e.g. `Add a (S b) = S (Add a b)`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Rule {
+  pub patterns: Vec<Pattern>,
+  pub body: Term,
+}
+
+// A single constructor of a data type, with its typed fields, e.g.
+// `S (pred: Nat) : Nat`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Constructor {
+  pub name: String,
+  pub args: Vec<(String, Term)>,
+}
+
+// A data type declaration, e.g. `Nat : Type` plus its `Z`/`S` constructors.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DataDecl {
+  pub name: String,
+  pub constructors: Vec<Constructor>,
+}
+
+// A function declaration: a type signature plus the rules defining it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FunDecl {
+  pub name: String,
+  pub signature: Term,
+  pub rules: Vec<Rule>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Decl {
+  Data(DataDecl),
+  Fun(FunDecl),
+}
@@ -0,0 +1,58 @@
+// Renders a `Span` into the source it came from as a caret-underlined
+// snippet, in the style of the Kind/bobbylisp toolchains:
+//
+//   1 | Add a (S b) = S (Add a c)
+//                              ^ unknown variable: c
+use crate::lexer::Span;
+
+pub fn report(source: &str, span: Span, message: &str) -> String {
+    let (line_number, column, line_text) = locate(source, span.start);
+    let underline_len = span.end.saturating_sub(span.start).max(1);
+
+    let gutter = format!("{} | ", line_number);
+    let padding = " ".repeat(gutter.len() + column);
+    let caret = "^".repeat(underline_len);
+
+    format!("{}{}\n{}{} {}", gutter, line_text, padding, caret, message)
+}
+
+// Finds the 1-indexed line number, 0-indexed column, and text of the line
+// containing character offset `start`.
+fn locate(source: &str, start: usize) -> (usize, usize, &str) {
+    let mut offset = 0;
+    for (i, line) in source.split('\n').enumerate() {
+        let line_len = line.chars().count();
+        // +1 accounts for the '\n' consumed between lines.
+        if start <= offset + line_len {
+            return (i + 1, start - offset, line);
+        }
+        offset += line_len + 1;
+    }
+    let last_line = source.split('\n').next_back().unwrap_or("");
+    (source.split('\n').count().max(1), last_line.chars().count(), last_line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_single_line() {
+        let source = "x -> @";
+        let rendered = report(source, Span::new(5, 6), "unexpected character");
+
+        assert_eq!(rendered, "1 | x -> @\n         ^ unexpected character");
+    }
+
+    #[test]
+    fn test_report_second_line() {
+        let source = "Nat : Type\nZ : Nat\nS (pred Nat) : Nat";
+        // The offending token is `pred` at offset 22 (line 3, column 3).
+        let rendered = report(source, Span::new(22, 26), "expected `:`");
+
+        assert_eq!(
+            rendered,
+            "3 | S (pred Nat) : Nat\n       ^^^^ expected `:`"
+        );
+    }
+}
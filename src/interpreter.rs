@@ -1,19 +1,47 @@
 use core::fmt;
 use std::{collections::HashMap, sync::Arc};
 
-use crate::ast::Term;
+use crate::ast::{free_vars, fresh_name, subst, Op, Term};
+use crate::decl::{FunDecl, Pattern};
 
 #[derive(Clone)]
 pub enum Value {
   Var(String),
   Closure(Arc<dyn Fn(Value) -> Value>),
+  Typ,
+  // A dependent function type, represented the same way as a closure so
+  // that the codomain can depend on the (stuck) variable it's probed with.
+  Pi(Box<Value>, Arc<dyn Fn(Value) -> Value>),
+  // A fully-applied data constructor, e.g. `S (S Z)`.
+  Ctr(String, Vec<Value>),
+  // A native machine number.
+  Num(u64),
+  // A primitive operator that couldn't be folded because one of its
+  // operands is still stuck on a free variable, e.g. `1 + x`.
+  Prim(Op, Box<Value>, Box<Value>),
+  // A function call that couldn't be reduced, either because `name` has no
+  // declared rules in scope or because none of its rules matched the given
+  // arguments (e.g. a partial application still stuck on a free variable).
+  Stuck(String, Vec<Value>),
+  // An application that couldn't be reduced because the thing being applied
+  // isn't a closure, e.g. `f x` where `f` is a free variable. Left as a
+  // residual the same way `Prim` leaves one on a free operand, rather than
+  // panicking.
+  App(Box<Value>, Box<Value>),
 }
 
 impl PartialEq for Value {
   fn eq(&self, other: &Self) -> bool {
     match (self, other) {
       (Value::Var(x), Value::Var(y)) => x == y,
+      (Value::Typ, Value::Typ) => true,
       (Value::Closure(_), Value::Closure(_)) => false,
+      (Value::Pi(_, _), Value::Pi(_, _)) => false,
+      (Value::Ctr(n1, a1), Value::Ctr(n2, a2)) => n1 == n2 && a1 == a2,
+      (Value::Num(a), Value::Num(b)) => a == b,
+      (Value::Prim(op1, l1, r1), Value::Prim(op2, l2, r2)) => op1 == op2 && l1 == l2 && r1 == r2,
+      (Value::Stuck(n1, a1), Value::Stuck(n2, a2)) => n1 == n2 && a1 == a2,
+      (Value::App(f1, a1), Value::App(f2, a2)) => f1 == f2 && a1 == a2,
       _ => false,
     }
   }
@@ -24,50 +52,222 @@ impl fmt::Debug for Value {
     match self {
       Value::Var(v) => write!(f, "Var({:?})", v),
       Value::Closure(_) => write!(f, "Closure(<function>)"),
+      Value::Typ => write!(f, "Typ"),
+      Value::Pi(domain, _) => write!(f, "Pi({:?}, <function>)", domain),
+      Value::Ctr(name, args) => write!(f, "Ctr({:?}, {:?})", name, args),
+      Value::Num(n) => write!(f, "Num({:?})", n),
+      Value::Prim(op, left, right) => write!(f, "Prim({:?}, {:?}, {:?})", op, left, right),
+      Value::Stuck(name, args) => write!(f, "Stuck({:?}, {:?})", name, args),
+      Value::App(func, arg) => write!(f, "App({:?}, {:?})", func, arg),
     }
   }
 }
 
 pub type Env = HashMap<String, Value>;
 
-pub fn eval(term: Term, env: &Env) -> Value {
+// The function declarations a program brought into scope, keyed by name, so
+// that `eval` can resolve a `Term::Fun` call to its rules.
+pub type Rules = HashMap<String, FunDecl>;
+
+pub fn eval(term: Term, env: &Env, rules: &Rules) -> Value {
   match term {
     Term::Var(x) => env.get(&x).cloned().unwrap_or(Value::Var(x)),
     Term::Abs(x, body) => {
       let env = env.clone();
+      let rules = rules.clone();
       Value::Closure(Arc::new(move |arg: Value| {
         let mut new_env = env.clone();
         new_env.insert(x.clone(), arg);
-        eval(*body.clone(), &new_env)
+        eval(*body.clone(), &new_env, &rules)
       }))
     }
     Term::App(t1, t2) => {
-      let func = eval(*t1, env);
-      let arg = eval(*t2, env);
+      let func = eval(*t1, env, rules);
+      let arg = eval(*t2, env, rules);
       match func {
         Value::Closure(f) => f(arg),
-        _ => panic!("Trying to apply a non-function"),
+        other => Value::App(Box::new(other), Box::new(arg)),
+      }
+    }
+    Term::Typ => Value::Typ,
+    Term::Pi { name, domain, codomain } => {
+      let domain_val = eval(*domain, env, rules);
+      let env = env.clone();
+      let rules = rules.clone();
+      Value::Pi(
+        Box::new(domain_val),
+        Arc::new(move |arg: Value| {
+          let mut new_env = env.clone();
+          new_env.insert(name.clone(), arg);
+          eval(*codomain.clone(), &new_env, &rules)
+        }),
+      )
+    }
+    // The annotation only guides type-checking; it's transparent to evaluation.
+    Term::Ann(term, _typ) => eval(*term, env, rules),
+    Term::Ctr { name, args } => {
+      let arg_values = args.into_iter().map(|arg| eval(arg, env, rules)).collect();
+      Value::Ctr(name, arg_values)
+    }
+    Term::Fun { name, args } => {
+      let arg_values: Vec<Value> = args.into_iter().map(|arg| eval(arg, env, rules)).collect();
+      match rules.get(&name) {
+        Some(decl) => eval_fun_call(decl, arg_values, rules),
+        None => Value::Stuck(name, arg_values),
+      }
+    }
+    Term::Lit(n) => Value::Num(n),
+    Term::Prim { op, left, right } => {
+      let left = eval(*left, env, rules);
+      let right = eval(*right, env, rules);
+      match (&left, &right) {
+        (Value::Num(a), Value::Num(b)) => Value::Num(apply_op(op, *a, *b)),
+        _ => Value::Prim(op, Box::new(left), Box::new(right)),
       }
     }
   }
 }
 
+// Folds two evaluated numeric operands; native numbers are `u64`s that wrap
+// rather than panicking on overflow.
+fn apply_op(op: Op, a: u64, b: u64) -> u64 {
+  match op {
+    Op::Add => a.wrapping_add(b),
+    Op::Sub => a.wrapping_sub(b),
+    Op::Mul => a.wrapping_mul(b),
+  }
+}
+
+// Tries each rule of `decl` left-to-right, binding pattern variables to the
+// matching sub-values, and evaluates the body of the first rule that matches.
+// If none does (e.g. an argument hasn't reduced far enough to match a
+// constructor pattern), the call is left as a stuck value rather than
+// aborting evaluation, the same way `Term::Prim` leaves a residual on a free
+// operand.
+fn eval_fun_call(decl: &FunDecl, arg_values: Vec<Value>, rules: &Rules) -> Value {
+  for rule in &decl.rules {
+    if rule.patterns.len() != arg_values.len() {
+      continue;
+    }
+    let mut bindings = Env::new();
+    let matched = rule
+      .patterns
+      .iter()
+      .zip(arg_values.iter())
+      .all(|(pattern, value)| match_pattern(pattern, value, &mut bindings));
+    if matched {
+      return eval(rule.body.clone(), &bindings, rules);
+    }
+  }
+  Value::Stuck(decl.name.clone(), arg_values)
+}
+
+fn match_pattern(pattern: &Pattern, value: &Value, bindings: &mut Env) -> bool {
+  match pattern {
+    Pattern::Var(name) => {
+      bindings.insert(name.clone(), value.clone());
+      true
+    }
+    Pattern::Ctr(ctr_name, sub_patterns) => match value {
+      Value::Ctr(name, arg_values) if name == ctr_name && arg_values.len() == sub_patterns.len() => sub_patterns
+        .iter()
+        .zip(arg_values.iter())
+        .all(|(sub_pattern, arg_value)| match_pattern(sub_pattern, arg_value, bindings)),
+      _ => false,
+    },
+  }
+}
+
 pub fn reify(val: Value) -> Term {
+  reify_fresh(val, &mut 0)
+}
+
+// Probes each closure with a placeholder `Var`, so nested binders don't
+// shadow one another (`\x. \x'. x`, not the bogus `\x. \x. x`), then picks
+// the binder's real printed name against the free variables actually
+// occurring in the reified body — not just a blind incrementing counter,
+// which could otherwise mint a name indistinguishable from an unrelated
+// free variable already present in the term (e.g. reifying `\a. \b. x'`
+// must not name the `b` binder `x'` too).
+fn reify_fresh(val: Value, counter: &mut usize) -> Term {
   match val {
     Value::Var(x) => Term::Var(x),
-      
+
     Value::Closure(f) => {
-      let dummy_var = Value::Var("x".to_string());
-      
-      let result = f(dummy_var.clone());
-      Term::Abs("x".to_string(), Box::new(reify(result)))
+      let placeholder = next_placeholder(counter);
+      let body = reify_fresh(f(Value::Var(placeholder.clone())), counter);
+      let (fresh_var, body) = bind_fresh(&placeholder, &[], body);
+      Term::Abs(fresh_var, Box::new(body))
     }
+
+    Value::Typ => Term::Typ,
+
+    Value::Pi(domain, codomain) => {
+      let placeholder = next_placeholder(counter);
+      let domain_term = reify_fresh(*domain, counter);
+      let codomain_term = reify_fresh(codomain(Value::Var(placeholder.clone())), counter);
+      let (fresh_var, codomain_term) = bind_fresh(&placeholder, &[&domain_term], codomain_term);
+      Term::Pi {
+        name: fresh_var,
+        domain: Box::new(domain_term),
+        codomain: Box::new(codomain_term),
+      }
+    }
+
+    Value::Ctr(name, arg_values) => {
+      let args = arg_values.into_iter().map(|v| reify_fresh(v, counter)).collect();
+      Term::Ctr { name, args }
+    }
+
+    Value::Num(n) => Term::Lit(n),
+
+    Value::Prim(op, left, right) => Term::Prim {
+      op,
+      left: Box::new(reify_fresh(*left, counter)),
+      right: Box::new(reify_fresh(*right, counter)),
+    },
+
+    Value::Stuck(name, arg_values) => {
+      let args = arg_values.into_iter().map(|v| reify_fresh(v, counter)).collect();
+      Term::Fun { name, args }
+    }
+
+    Value::App(func, arg) => Term::App(
+      Box::new(reify_fresh(*func, counter)),
+      Box::new(reify_fresh(*arg, counter)),
+    ),
+  }
+}
+
+// Mints a probe name that's unique for the lifetime of one `reify` call and
+// can never collide with a variable the real lexer can produce (identifiers
+// are always alphabetic), so every occurrence of it in `body` is guaranteed
+// to be one we just introduced, not an unrelated free variable.
+fn next_placeholder(counter: &mut usize) -> String {
+  let name = format!("#{}", counter);
+  *counter += 1;
+  name
+}
+
+// Replaces every occurrence of `placeholder` in `body` with a name that's
+// actually fresh with respect to the free variables occurring in `body`
+// and in `also_avoid` (e.g. a `Pi`'s already-reified domain), the same way
+// `ast::subst` picks a capture-avoiding name against `ast::free_vars`.
+fn bind_fresh(placeholder: &str, also_avoid: &[&Term], body: Term) -> (String, Term) {
+  let mut avoid = free_vars(&body);
+  for term in also_avoid {
+    avoid.extend(free_vars(term));
   }
+  avoid.remove(placeholder);
+  let fresh_var = fresh_name("x", &avoid);
+  let body = subst(placeholder, &Term::Var(fresh_var.clone()), &body);
+  (fresh_var, body)
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::decl::Rule;
 
   #[test]
   fn test_variable_evaluation() {
@@ -75,7 +275,7 @@ mod tests {
     env.insert("x".to_string(), Value::Var("x_value".to_string()));
 
     let term = Term::Var("x".to_string());
-    let result = eval(term, &env);
+    let result = eval(term, &env, &Rules::new());
 
     assert_eq!(result, Value::Var("x_value".to_string()));
   }
@@ -87,7 +287,7 @@ mod tests {
     
     let app = Term::App(Box::new(identity), Box::new(Term::Var("y".to_string())));  // Apply λx.x to "y"
 
-    let result = eval(app, &env);
+    let result = eval(app, &env, &Rules::new());
 
     assert_eq!(result, Value::Var("y".to_string()));
   }
@@ -108,7 +308,7 @@ mod tests {
     let app1 = Term::App(Box::new(constant_func), Box::new(Term::Var("a".to_string())));  // Apply (λx. λy. x) to "a"
     let app2 = Term::App(Box::new(app1), Box::new(Term::Var("b".to_string())));  // Apply the result to "b"
 
-    let result = eval(app2, &env);
+    let result = eval(app2, &env, &Rules::new());
     assert_eq!(result, Value::Var("a".to_string()));
   }
 
@@ -121,7 +321,7 @@ mod tests {
 
     // Apply closure to "anything"
     let app = Term::App(Box::new(closure_with_env), Box::new(Term::Var("ignored".to_string())));
-    let result = eval(app, &env);
+    let result = eval(app, &env, &Rules::new());
     assert_eq!(result, Value::Var("z_value".to_string()));
   }
 
@@ -161,13 +361,217 @@ mod tests {
     let nested_closure = Value::Closure(Arc::new(|arg: Value| {
       Value::Closure(Arc::new(move |_arg2: Value| arg.clone()))
     }));
-    
+
     let reified = reify(nested_closure);
     assert_eq!(
       reified,
       Term::Abs(
         "x".to_string(),
-        Box::new(Term::Abs("x".to_string(), Box::new(Term::Var("x".to_string())))) // λx. λx. x
+        Box::new(Term::Abs("x'".to_string(), Box::new(Term::Var("x".to_string())))) // λx. λx'. x
+      )
+    );
+  }
+
+  #[test]
+  fn test_reify_does_not_name_a_binder_after_an_unrelated_free_variable() {
+    // \a. \b. x', where `x'` is a genuinely free variable unrelated to
+    // either binder. A blind `x`, `x'`, `x''`, ... counter would name the
+    // second binder `x'` too, making it indistinguishable from the free
+    // variable it shadows in the printed term.
+    let outer = Value::Closure(Arc::new(|_a: Value| {
+      Value::Closure(Arc::new(|_b: Value| Value::Var("x'".to_string())))
+    }));
+
+    let reified = reify(outer);
+    assert_eq!(free_vars(&reified), {
+      let mut vars = std::collections::HashSet::new();
+      vars.insert("x'".to_string());
+      vars
+    });
+  }
+
+  #[test]
+  fn test_eval_and_reify_pi_type() {
+    let env = Env::new();
+
+    // (a : Type) -> a
+    let pi = Term::Pi {
+      name: "a".to_string(),
+      domain: Box::new(Term::Typ),
+      codomain: Box::new(Term::Var("a".to_string())),
+    };
+
+    let result = eval(pi, &env, &Rules::new());
+    let reified = reify(result);
+
+    assert_eq!(
+      reified,
+      Term::Pi {
+        name: "x".to_string(),
+        domain: Box::new(Term::Typ),
+        codomain: Box::new(Term::Var("x".to_string())),
+      }
+    );
+  }
+
+  #[test]
+  fn test_eval_fun_call_with_pattern_matching() {
+    // Add a Z = a
+    // Add a (S b) = S (Add a b)
+    let add = FunDecl {
+      name: "Add".to_string(),
+      signature: Term::Typ, // not exercised by eval
+      rules: vec![
+        Rule {
+          patterns: vec![Pattern::Var("a".to_string()), Pattern::Ctr("Z".to_string(), vec![])],
+          body: Term::Var("a".to_string()),
+        },
+        Rule {
+          patterns: vec![
+            Pattern::Var("a".to_string()),
+            Pattern::Ctr("S".to_string(), vec![Pattern::Var("b".to_string())]),
+          ],
+          body: Term::Ctr {
+            name: "S".to_string(),
+            args: vec![Term::Fun {
+              name: "Add".to_string(),
+              args: vec![Term::Var("a".to_string()), Term::Var("b".to_string())],
+            }],
+          },
+        },
+      ],
+    };
+    let mut rules = Rules::new();
+    rules.insert("Add".to_string(), add);
+
+    // Add (S Z) (S Z) should reduce to S (S Z), i.e. 1 + 1 = 2.
+    let one = Term::Ctr { name: "S".to_string(), args: vec![Term::Ctr { name: "Z".to_string(), args: vec![] }] };
+    let term = Term::Fun { name: "Add".to_string(), args: vec![one.clone(), one] };
+
+    let result = eval(term, &Env::new(), &rules);
+    assert_eq!(
+      result,
+      Value::Ctr(
+        "S".to_string(),
+        vec![Value::Ctr("S".to_string(), vec![Value::Ctr("Z".to_string(), vec![])])]
+      )
+    );
+  }
+
+  #[test]
+  fn test_eval_prim_folds_numeric_literals() {
+    use crate::ast::Op;
+
+    // 2 * 3 + 1
+    let term = Term::Prim {
+      op: Op::Add,
+      left: Box::new(Term::Prim {
+        op: Op::Mul,
+        left: Box::new(Term::Lit(2)),
+        right: Box::new(Term::Lit(3)),
+      }),
+      right: Box::new(Term::Lit(1)),
+    };
+
+    let result = eval(term, &Env::new(), &Rules::new());
+    assert_eq!(result, Value::Num(7));
+  }
+
+  #[test]
+  fn test_eval_prim_leaves_residual_on_free_variable() {
+    use crate::ast::Op;
+
+    // 1 + x, with `x` free, can't reduce any further.
+    let term = Term::Prim {
+      op: Op::Add,
+      left: Box::new(Term::Lit(1)),
+      right: Box::new(Term::Var("x".to_string())),
+    };
+
+    let result = eval(term, &Env::new(), &Rules::new());
+    assert_eq!(
+      result,
+      Value::Prim(Op::Add, Box::new(Value::Num(1)), Box::new(Value::Var("x".to_string())))
+    );
+    assert_eq!(
+      reify(result),
+      Term::Prim {
+        op: Op::Add,
+        left: Box::new(Term::Lit(1)),
+        right: Box::new(Term::Var("x".to_string())),
+      }
+    );
+  }
+
+  #[test]
+  fn test_eval_fun_call_with_unknown_function_is_stuck() {
+    // Calling a name with no rules in scope (e.g. a typo) leaves a stuck
+    // value instead of panicking.
+    let term = Term::Fun { name: "Missing".to_string(), args: vec![Term::Lit(1)] };
+    let result = eval(term, &Env::new(), &Rules::new());
+    assert_eq!(result, Value::Stuck("Missing".to_string(), vec![Value::Num(1)]));
+    assert_eq!(
+      reify(result),
+      Term::Fun { name: "Missing".to_string(), args: vec![Term::Lit(1)] }
+    );
+  }
+
+  #[test]
+  fn test_eval_fun_call_with_no_matching_rule_is_stuck() {
+    // Add Z b = b
+    // Only matches when the first argument is `Z`, so a free variable in
+    // that position can't match any rule and leaves the call stuck.
+    let add = FunDecl {
+      name: "Add".to_string(),
+      signature: Term::Typ,
+      rules: vec![Rule {
+        patterns: vec![Pattern::Ctr("Z".to_string(), vec![]), Pattern::Var("b".to_string())],
+        body: Term::Var("b".to_string()),
+      }],
+    };
+    let mut rules = Rules::new();
+    rules.insert("Add".to_string(), add);
+
+    let term = Term::Fun {
+      name: "Add".to_string(),
+      args: vec![Term::Var("x".to_string()), Term::Lit(1)],
+    };
+    let result = eval(term, &Env::new(), &rules);
+    assert_eq!(
+      result,
+      Value::Stuck("Add".to_string(), vec![Value::Var("x".to_string()), Value::Num(1)])
+    );
+  }
+
+  #[test]
+  fn test_eval_app_of_free_variable_is_stuck() {
+    // `f x`, with `f` a free variable rather than a closure, can't reduce
+    // any further — it should leave a residual application instead of
+    // panicking.
+    let term = Term::App(Box::new(Term::Var("f".to_string())), Box::new(Term::Var("x".to_string())));
+    let result = eval(term, &Env::new(), &Rules::new());
+    assert_eq!(
+      result,
+      Value::App(Box::new(Value::Var("f".to_string())), Box::new(Value::Var("x".to_string())))
+    );
+    assert_eq!(
+      reify(result),
+      Term::App(Box::new(Term::Var("f".to_string())), Box::new(Term::Var("x".to_string())))
+    );
+  }
+
+  #[test]
+  fn test_eval_app_chain_on_free_variable_stays_stuck() {
+    // `f x y`, i.e. `(f x) y`, should stay stuck through both applications
+    // rather than panicking on either one.
+    let inner = Term::App(Box::new(Term::Var("f".to_string())), Box::new(Term::Var("x".to_string())));
+    let term = Term::App(Box::new(inner), Box::new(Term::Var("y".to_string())));
+    let result = eval(term, &Env::new(), &Rules::new());
+    assert_eq!(
+      reify(result),
+      Term::App(
+        Box::new(Term::App(Box::new(Term::Var("f".to_string())), Box::new(Term::Var("x".to_string())))),
+        Box::new(Term::Var("y".to_string())),
       )
     );
   }
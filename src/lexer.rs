@@ -1,3 +1,18 @@
+// A half-open range of character offsets into the original source string,
+// used to point diagnostics at the exact text that produced a token or
+// error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     Lambda,
@@ -5,7 +20,24 @@ pub enum Token {
     LeftParen,
     RightParen,
     Identifier(String),
+    Type,
+    Arrow,
+    Colon,
+    Equals,
+    Newline,
+    Indent(usize),
+    Number(u64),
+    Plus,
+    Minus,
+    Star,
 }
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LexError {
+    pub message: String,
+    pub span: Span,
+}
+
 pub struct Lexer {
     input: Vec<char>,
     position: usize,
@@ -19,35 +51,93 @@ impl Lexer {
         }
     }
 
-    pub fn tokenize(&mut self) -> Result<Vec<Token>, String> {
+    pub fn tokenize(&mut self) -> Result<Vec<(Token, Span)>, LexError> {
         let mut tokens = Vec::new();
 
         while let Some(ch) = self.peek() {
+            let start = self.position;
             match ch {
                 '\\' => {
                     self.advance();
-                    tokens.push(Token::Lambda);
+                    tokens.push((Token::Lambda, Span::new(start, self.position)));
                 }
                 '.' => {
                     self.advance();
-                    tokens.push(Token::Dot);
+                    tokens.push((Token::Dot, Span::new(start, self.position)));
                 }
                 '(' => {
                     self.advance();
-                    tokens.push(Token::LeftParen);
+                    tokens.push((Token::LeftParen, Span::new(start, self.position)));
                 }
                 ')' => {
                     self.advance();
-                    tokens.push(Token::RightParen);
+                    tokens.push((Token::RightParen, Span::new(start, self.position)));
+                }
+                ':' => {
+                    self.advance();
+                    tokens.push((Token::Colon, Span::new(start, self.position)));
+                }
+                '=' => {
+                    self.advance();
+                    tokens.push((Token::Equals, Span::new(start, self.position)));
+                }
+                '-' => {
+                    self.advance();
+                    if self.peek() == Some('>') {
+                        self.advance();
+                        tokens.push((Token::Arrow, Span::new(start, self.position)));
+                    } else {
+                        tokens.push((Token::Minus, Span::new(start, self.position)));
+                    }
+                }
+                '+' => {
+                    self.advance();
+                    tokens.push((Token::Plus, Span::new(start, self.position)));
+                }
+                '*' => {
+                    self.advance();
+                    tokens.push((Token::Star, Span::new(start, self.position)));
+                }
+                '\n' => {
+                    self.advance();
+                    tokens.push((Token::Newline, Span::new(start, self.position)));
+
+                    let indent_start = self.position;
+                    let mut indent = 0;
+                    while matches!(self.peek(), Some(' ') | Some('\t')) {
+                        self.advance();
+                        indent += 1;
+                    }
+                    if indent > 0 {
+                        tokens.push((Token::Indent(indent), Span::new(indent_start, self.position)));
+                    }
                 }
                 c if c.is_whitespace() => {
                     self.advance();
                 }
                 c if c.is_alphabetic() => {
                     let identifier = self.read_identifier();
-                    tokens.push(Token::Identifier(identifier));
+                    let span = Span::new(start, self.position);
+                    if identifier == "Type" {
+                        tokens.push((Token::Type, span));
+                    } else {
+                        tokens.push((Token::Identifier(identifier), span));
+                    }
+                }
+                c if c.is_ascii_digit() => {
+                    let number = self.read_number();
+                    let span = Span::new(start, self.position);
+                    match number {
+                        Ok(n) => tokens.push((Token::Number(n), span)),
+                        Err(message) => return Err(LexError { message, span }),
+                    }
+                }
+                _ => {
+                    return Err(LexError {
+                        message: format!("Unexpected character: {}", ch),
+                        span: Span::new(start, start + 1),
+                    })
                 }
-                _ => return Err(format!("Unexpected character: {}", ch)),
             }
         }
 
@@ -73,17 +163,30 @@ impl Lexer {
         }
         self.input[start..self.position].iter().collect()
     }
+
+    fn read_number(&mut self) -> Result<u64, String> {
+        let start = self.position;
+        while matches!(self.peek(), Some(ch) if ch.is_ascii_digit()) {
+            self.advance();
+        }
+        let text: String = self.input[start..self.position].iter().collect();
+        text.parse().map_err(|_| format!("Numeric literal out of range: {}", text))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn tokens_only(tokens: Vec<(Token, Span)>) -> Vec<Token> {
+        tokens.into_iter().map(|(token, _)| token).collect()
+    }
+
     #[test]
     fn test_tokenize() {
         let input = "(\\x. x y) (\\z. z)";
         let mut lexer = Lexer::new(input);
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = tokens_only(lexer.tokenize().unwrap());
 
         assert_eq!(
             tokens,
@@ -104,4 +207,119 @@ mod tests {
             ]
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_tokenize_pi_type() {
+        let input = "(a: Type) -> a";
+        let mut lexer = Lexer::new(input);
+        let tokens = tokens_only(lexer.tokenize().unwrap());
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::LeftParen,
+                Token::Identifier("a".to_string()),
+                Token::Colon,
+                Token::Type,
+                Token::RightParen,
+                Token::Arrow,
+                Token::Identifier("a".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_data_decl() {
+        let input = "Nat : Type\n  Z : Nat";
+        let mut lexer = Lexer::new(input);
+        let tokens = tokens_only(lexer.tokenize().unwrap());
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("Nat".to_string()),
+                Token::Colon,
+                Token::Type,
+                Token::Newline,
+                Token::Indent(2),
+                Token::Identifier("Z".to_string()),
+                Token::Colon,
+                Token::Identifier("Nat".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_rule_line() {
+        let input = "Add a Z = a";
+        let mut lexer = Lexer::new(input);
+        let tokens = tokens_only(lexer.tokenize().unwrap());
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("Add".to_string()),
+                Token::Identifier("a".to_string()),
+                Token::Identifier("Z".to_string()),
+                Token::Equals,
+                Token::Identifier("a".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_spans() {
+        let input = "x -> @";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize().unwrap_err();
+
+        // "x -> @": the offending '@' sits at offset 5.
+        assert_eq!(tokens.span, Span::new(5, 6));
+    }
+
+    #[test]
+    fn test_tokenize_identifier_span() {
+        let input = "foo bar";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize().unwrap();
+
+        assert_eq!(tokens[0], (Token::Identifier("foo".to_string()), Span::new(0, 3)));
+        assert_eq!(tokens[1], (Token::Identifier("bar".to_string()), Span::new(4, 7)));
+    }
+
+    #[test]
+    fn test_tokenize_numbers_and_operators() {
+        let input = "1 + 2 * 3 - 4";
+        let mut lexer = Lexer::new(input);
+        let tokens = tokens_only(lexer.tokenize().unwrap());
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Number(1),
+                Token::Plus,
+                Token::Number(2),
+                Token::Star,
+                Token::Number(3),
+                Token::Minus,
+                Token::Number(4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_bare_minus_is_not_an_error() {
+        let input = "x - y";
+        let mut lexer = Lexer::new(input);
+        let tokens = tokens_only(lexer.tokenize().unwrap());
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("x".to_string()),
+                Token::Minus,
+                Token::Identifier("y".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+// A Krivine-style call-by-name abstract machine: an explicit, step-inspectable
+// alternative to `interpreter::eval`'s native Rust closures. Terms are first
+// compiled to de Bruijn indices so environments can be plain stacks of
+// thunks instead of name-keyed maps.
+use std::collections::HashSet;
+
+use crate::ast::{free_vars, Term};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ITerm {
+  // A variable bound `index` binders out from its occurrence (0 = the
+  // innermost enclosing `Abs`).
+  Var(usize),
+  // A name with no enclosing binder, carried through unevaluated, the same
+  // way `interpreter::eval` treats an unbound `Term::Var`.
+  Free(String),
+  Abs(Box<ITerm>),
+  App(Box<ITerm>, Box<ITerm>),
+}
+
+// A suspended computation: code paired with the environment it closes over.
+#[derive(Clone)]
+struct Closure {
+  code: ITerm,
+  env: Vec<Closure>,
+}
+
+// A `Term` this machine has no de Bruijn-index encoding for yet (anything
+// beyond the untyped lambda calculus core: `Typ`, `Pi`, `Ctr`, `Fun`, `Lit`,
+// `Prim`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsupportedTerm(pub Term);
+
+// Resolves every `Var(name)` to a de Bruijn index (or leaves it as `Free` if
+// no enclosing binder shares its name); `Ann` is transparent, matching `eval`.
+pub fn compile(term: &Term) -> Result<ITerm, UnsupportedTerm> {
+  compile_with(term, &mut Vec::new())
+}
+
+fn compile_with(term: &Term, scope: &mut Vec<String>) -> Result<ITerm, UnsupportedTerm> {
+  match term {
+    Term::Var(name) => match scope.iter().rev().position(|bound| bound == name) {
+      Some(index) => Ok(ITerm::Var(index)),
+      None => Ok(ITerm::Free(name.clone())),
+    },
+    Term::Abs(param, body) => {
+      scope.push(param.clone());
+      let compiled_body = compile_with(body, scope);
+      scope.pop();
+      Ok(ITerm::Abs(Box::new(compiled_body?)))
+    }
+    Term::App(func, arg) => Ok(ITerm::App(
+      Box::new(compile_with(func, scope)?),
+      Box::new(compile_with(arg, scope)?),
+    )),
+    Term::Ann(term, _typ) => compile_with(term, scope),
+    other => Err(UnsupportedTerm(other.clone())),
+  }
+}
+
+// Runs the machine's three transition rules until it reaches weak head
+// normal form: `code` is stuck on a free variable, or it's an `Abs` with no
+// more arguments on the stack to consume.
+fn whnf(code: ITerm, env: Vec<Closure>, mut stack: Vec<Closure>) -> (ITerm, Vec<Closure>, Vec<Closure>) {
+  match code {
+    ITerm::App(func, arg) => {
+      stack.push(Closure { code: *arg, env: env.clone() });
+      whnf(*func, env, stack)
+    }
+    ITerm::Abs(body) => match stack.pop() {
+      Some(arg) => {
+        let mut env = env;
+        env.push(arg);
+        whnf(*body, env, stack)
+      }
+      None => (ITerm::Abs(body), env, stack),
+    },
+    ITerm::Var(index) => {
+      let closure = env[env.len() - 1 - index].clone();
+      whnf(closure.code, closure.env, stack)
+    }
+    ITerm::Free(name) => (ITerm::Free(name), env, stack),
+  }
+}
+
+// Reads a weak head normal form back out to a full normal form by
+// recursively normalizing under binders, probing each with a free variable
+// that is fresh with respect to `avoid` (the free variables of the whole
+// term) and to every other probe, as `interpreter::reify` also does.
+fn normal_form(
+  code: ITerm,
+  env: Vec<Closure>,
+  stack: Vec<Closure>,
+  counter: &mut usize,
+  avoid: &HashSet<String>,
+) -> Term {
+  let (head, head_env, mut remaining) = whnf(code, env, stack);
+  match head {
+    ITerm::Abs(body) => {
+      let fresh = fresh_name(counter, avoid);
+      let mut inner_env = head_env;
+      inner_env.push(Closure { code: ITerm::Free(fresh.clone()), env: Vec::new() });
+      let body_term = normal_form(*body, inner_env, Vec::new(), counter, avoid);
+      Term::Abs(fresh, Box::new(body_term))
+    }
+    ITerm::Free(name) => {
+      let mut result = Term::Var(name);
+      while let Some(closure) = remaining.pop() {
+        let arg_term = normal_form(closure.code, closure.env, Vec::new(), counter, avoid);
+        result = Term::App(Box::new(result), Box::new(arg_term));
+      }
+      result
+    }
+    ITerm::Var(_) | ITerm::App(_, _) => unreachable!("whnf always reduces Var/App away"),
+  }
+}
+
+// Mints `x`, `x'`, `x''`, ... in turn, skipping any name in `avoid`.
+fn fresh_name(counter: &mut usize, avoid: &HashSet<String>) -> String {
+  loop {
+    let name = format!("x{}", "'".repeat(*counter));
+    *counter += 1;
+    if !avoid.contains(&name) {
+      return name;
+    }
+  }
+}
+
+// Compiles and fully normalizes `term` on the Krivine machine, returning the
+// same kind of normal form `reify(eval(term, &Env::new(), &Rules::new()))`
+// would.
+pub fn eval_krivine(term: Term) -> Result<Term, UnsupportedTerm> {
+  let compiled = compile(&term)?;
+  let avoid = free_vars(&term);
+  Ok(normal_form(compiled, Vec::new(), Vec::new(), &mut 0, &avoid))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::interpreter::{eval, reify, Env, Rules};
+
+  fn eval_reify(term: Term) -> Term {
+    reify(eval(term, &Env::new(), &Rules::new()))
+  }
+
+  #[test]
+  fn test_krivine_agrees_on_identity() {
+    let identity = Term::Abs("x".to_string(), Box::new(Term::Var("x".to_string())));
+    let app = Term::App(Box::new(identity.clone()), Box::new(Term::Var("y".to_string())));
+
+    assert_eq!(eval_krivine(app.clone()).unwrap(), eval_reify(app));
+    assert_eq!(eval_krivine(identity.clone()).unwrap(), eval_reify(identity));
+  }
+
+  #[test]
+  fn test_krivine_agrees_on_constant() {
+    // λx. λy. x, applied to "a" and "b".
+    let constant = Term::Abs(
+      "x".to_string(),
+      Box::new(Term::Abs("y".to_string(), Box::new(Term::Var("x".to_string())))),
+    );
+    let app1 = Term::App(Box::new(constant), Box::new(Term::Var("a".to_string())));
+    let app2 = Term::App(Box::new(app1), Box::new(Term::Var("b".to_string())));
+
+    assert_eq!(eval_krivine(app2.clone()).unwrap(), eval_reify(app2));
+  }
+
+  #[test]
+  fn test_krivine_agrees_on_nested() {
+    // λx. λy. x, left unapplied, so normalization must descend under both
+    // binders with fresh names.
+    let nested = Term::Abs(
+      "x".to_string(),
+      Box::new(Term::Abs("y".to_string(), Box::new(Term::Var("x".to_string())))),
+    );
+
+    assert_eq!(eval_krivine(nested.clone()).unwrap(), eval_reify(nested));
+    assert_eq!(
+      eval_krivine(Term::Abs(
+        "x".to_string(),
+        Box::new(Term::Abs("y".to_string(), Box::new(Term::Var("x".to_string()))))
+      ))
+      .unwrap(),
+      Term::Abs(
+        "x".to_string(),
+        Box::new(Term::Abs("x'".to_string(), Box::new(Term::Var("x".to_string()))))
+      )
+    );
+  }
+
+  #[test]
+  fn test_krivine_leaves_stuck_application_chain_intact() {
+    // `f x y`, with `f` free, has nothing to reduce and should round-trip.
+    let term = Term::App(
+      Box::new(Term::App(
+        Box::new(Term::Var("f".to_string())),
+        Box::new(Term::Var("x".to_string())),
+      )),
+      Box::new(Term::Var("y".to_string())),
+    );
+
+    assert_eq!(eval_krivine(term.clone()).unwrap(), term);
+  }
+
+  #[test]
+  fn test_compile_reports_unsupported_term_instead_of_panicking() {
+    // The machine only has a de Bruijn encoding for the untyped lambda
+    // calculus core (`Var`/`Abs`/`App`/`Ann`); anything else, like a native
+    // literal, should come back as an error rather than unwind the session.
+    let term = Term::Lit(5);
+    assert_eq!(compile(&term), Err(UnsupportedTerm(Term::Lit(5))));
+    assert_eq!(eval_krivine(term), Err(UnsupportedTerm(Term::Lit(5))));
+  }
+}
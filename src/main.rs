@@ -1,86 +1,376 @@
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
 use std::fs;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Write};
 use std::path::Path;
 
 mod ast;
+mod decl;
+mod diagnostics;
 mod interpreter;
+mod lexer;
+mod machine;
+mod parser;
+mod typecheck;
 
-use interpreter::{eval, reify, Env};
 use ast::Term;
+use decl::{Constructor, Decl};
+use interpreter::{eval, reify, Env, Rules, Value};
+use parser::{parse_with_funs, ParseError, ParseErrorKind};
+use typecheck::Ctx;
 
-fn parse_term(code: &str) -> Term {
-    match code {
-        "\\x. x" => Term::Abs("x".to_string(), Box::new(Term::Var("x".to_string()))),
-        "\\x. \\y. x" => Term::Abs("x".to_string(), Box::new(Term::Abs("y".to_string(), Box::new(Term::Var("x".to_string()))))),
-        "(\\x. x) z" => Term::App(Box::new(Term::Abs("x".to_string(), Box::new(Term::Var("x".to_string())))), Box::new(Term::Var("z".to_string()))),
-        "(\\x. \\y. x) a b" => {
-            let nested = Term::App(
-                Box::new(Term::Abs("x".to_string(), Box::new(Term::Abs("y".to_string(), Box::new(Term::Var("x".to_string())))))),
-                Box::new(Term::Var("a".to_string()))
-            );
-            Term::App(Box::new(nested), Box::new(Term::Var("b".to_string())))
+// Persistent interpreter state shared across a REPL session or a file run:
+// `let`-bound values, whatever function declarations `:load` has brought
+// into scope, and the type signatures (`ctx`) those declarations carry.
+struct Session {
+  env: Env,
+  rules: Rules,
+  funs: HashSet<String>,
+  ctx: Ctx,
+}
+
+impl Session {
+  fn new() -> Self {
+    Session { env: Env::new(), rules: Rules::new(), funs: HashSet::new(), ctx: Ctx::new() }
+  }
+
+  // Parses `source`, resolving any capitalized call against the function
+  // names this session already knows about (see `parser::parse_with_funs`).
+  fn parse(&self, source: &str) -> Result<Term, ParseError> {
+    parse_with_funs(source, self.funs.clone())
+  }
+
+  // Parses and evaluates `source` against the session's bindings and
+  // rules, without reifying the result (so a `let` can store the raw
+  // `Value`).
+  fn eval_source(&self, source: &str) -> Result<Value, ParseError> {
+    let term = self.parse(source)?;
+    Ok(eval(term, &self.env, &self.rules))
+  }
+
+  // Parses `path` as a declaration program (see `parser::parse_program`)
+  // and brings every `Decl` it defines into scope: a `Decl::Fun` adds its
+  // rules (so `eval` can reduce calls to it) and its signature to `ctx`;
+  // a `Decl::Data` adds the type itself plus every constructor's signature
+  // to `ctx`, so `typecheck::infer`/`check` can see both. Returns how many
+  // declarations were loaded.
+  fn load(&mut self, path: &Path) -> Result<usize, RunError> {
+    let source = fs::read_to_string(path)?;
+    let decls = parser::parse_program(&source)?;
+    let mut loaded = 0;
+    for decl in decls {
+      match decl {
+        Decl::Fun(fun_decl) => {
+          self.funs.insert(fun_decl.name.clone());
+          self.ctx.insert(fun_decl.name.clone(), fun_decl.signature.clone());
+          self.rules.insert(fun_decl.name.clone(), fun_decl);
+        }
+        Decl::Data(data_decl) => {
+          self.ctx.insert(data_decl.name.clone(), Term::Typ);
+          for ctr in &data_decl.constructors {
+            self.ctx.insert(ctr.name.clone(), constructor_signature(ctr, &data_decl.name));
+          }
         }
-        _ => panic!("Unknown expression: {}", code),
+      }
+      loaded += 1;
     }
+    Ok(loaded)
+  }
+}
+
+// Builds a constructor's function type from its typed argument list, e.g.
+// `S (pred: Nat) : Nat` becomes `(pred : Nat) -> Nat`, the same `Pi`-chain
+// shape `typecheck::infer` already expects to find for a `Term::Ctr` name.
+fn constructor_signature(ctr: &Constructor, data_name: &str) -> Term {
+  ctr.args.iter().rev().fold(Term::Var(data_name.to_string()), |codomain, (name, domain)| Term::Pi {
+    name: name.clone(),
+    domain: Box::new(domain.clone()),
+    codomain: Box::new(codomain),
+  })
+}
+
+// Recognizes a top-level `let name = term` binding, splitting it into the
+// bound name and the (trimmed) term text that follows the `=`.
+fn parse_let(line: &str) -> Option<(&str, &str)> {
+  let rest = line.strip_prefix("let ")?;
+  let (name, body) = rest.split_once('=')?;
+  Some((name.trim(), body.trim()))
+}
+
+#[derive(Debug)]
+enum RunError {
+  Io(io::Error),
+  Parse(ParseError),
+  // A file with no expression line (only `let` and `:load` lines, or none),
+  // so there's nothing to report a result for.
+  Empty,
+}
+
+impl From<io::Error> for RunError {
+  fn from(err: io::Error) -> Self {
+    RunError::Io(err)
+  }
+}
+
+impl From<ParseError> for RunError {
+  fn from(err: ParseError) -> Self {
+    RunError::Parse(err)
+  }
 }
 
-fn interpret_file(file_path: &Path) -> Result<Term, io::Error> {
-    let file = fs::File::open(file_path)?;
-    let env = Env::new();
-    let reader = io::BufReader::new(file);
-
-    for line in reader.lines() {
-        let line = line?;
-        let term = parse_term(&line);
-        let result = eval(term, &env);
-        let reified = reify(result);
-        return Ok(reified);
+impl fmt::Display for RunError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      RunError::Io(err) => write!(f, "{}", err),
+      RunError::Parse(err) => write!(f, "{}", describe_parse_error(&err.kind)),
+      RunError::Empty => write!(f, "file has no expression to evaluate"),
     }
-    Err(io::Error::new(io::ErrorKind::Other, "Empty file"))
+  }
 }
 
-fn main() -> io::Result<()> {
-    let test_files = vec!["identity.lisp", "constant.txt", "capture.txt", "nested.txt"];
-    for test_file in test_files {
-        let path = Path::new(test_file);
-        match interpret_file(path) {
-            Ok(term) => println!("Interpreted: {:?}", term),
-            Err(e) => eprintln!("Error interpreting file {}: {}", test_file, e),
-        }
+// Runs every line of `file_path` against a shared session: `:load` and
+// `let` lines are handled as in the REPL, and every other line is evaluated
+// as an expression (the REPL's other `:` commands are not recognized).
+// Returns the normal form of the last expression line.
+fn interpret_file(file_path: &Path) -> Result<Term, RunError> {
+  let file = fs::File::open(file_path)?;
+  let reader = io::BufReader::new(file);
+  let mut session = Session::new();
+  let mut last_result = None;
+
+  for line in reader.lines() {
+    let line = line?;
+    let line = line.trim();
+    if line.is_empty() {
+      continue;
     }
-    Ok(())
+
+    if let Some(path) = line.strip_prefix(":load ") {
+      session.load(Path::new(path.trim()))?;
+    } else if let Some((name, body)) = parse_let(line) {
+      let value = session.eval_source(body)?;
+      session.env.insert(name.to_string(), value);
+    } else {
+      last_result = Some(reify(session.eval_source(line)?));
+    }
+  }
+
+  last_result.ok_or(RunError::Empty)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::Path;
-
-    #[test]
-    fn test_interpret_identity() {
-        let path = Path::new("/Users/alanbertani/dev/DepenLang/test_files/identity.lisp");
-        let result = interpret_file(path).unwrap();
-        assert_eq!(result, Term::Abs("x".to_string(), Box::new(Term::Var("x".to_string()))));
+fn describe_parse_error(kind: &ParseErrorKind) -> String {
+  match kind {
+    ParseErrorKind::UnexpectedToken(token) => format!("unexpected token: {:?}", token),
+    ParseErrorKind::UnexpectedEndOfInput => "unexpected end of input".to_string(),
+    ParseErrorKind::InvalidExpression => "invalid expression".to_string(),
+    ParseErrorKind::LexError(message) => message.clone(),
+  }
+}
+
+fn report_parse_error(source: &str, err: &ParseError) {
+  eprintln!("{}", diagnostics::report(source, err.span, &describe_parse_error(&err.kind)));
+}
+
+// Dispatches one line of REPL input: a `:`-prefixed command, a `let`
+// binding, or a bare expression to normalize and print.
+fn handle_line(line: &str, session: &mut Session) {
+  if let Some(expr) = line.strip_prefix(":tree ") {
+    match session.parse(expr.trim()) {
+      Ok(term) => print!("{}", term.ascii_tree()),
+      Err(err) => report_parse_error(expr.trim(), &err),
+    }
+    return;
+  }
+
+  if let Some(expr) = line.strip_prefix(":type ") {
+    let expr = expr.trim();
+    match session.parse(expr) {
+      Ok(term) => match typecheck::infer(&session.ctx, &term) {
+        Ok(typ) => println!("{}", typ.pretty_print()),
+        Err(err) => println!("{:?}", err),
+      },
+      Err(err) => report_parse_error(expr, &err),
     }
+    return;
+  }
 
-    #[test]
-    fn test_interpret_constant() {
-      let path = Path::new("/Users/alanbertani/dev/DepenLang/test_files/constant.lisp");
-      let result = interpret_file(path).unwrap();
-        assert_eq!(result, Term::Abs("x".to_string(), Box::new(Term::Abs("y".to_string(), Box::new(Term::Var("x".to_string()))))));
+  if let Some(expr) = line.strip_prefix(":krivine ") {
+    let expr = expr.trim();
+    match session.parse(expr) {
+      Ok(term) => match machine::eval_krivine(term) {
+        Ok(result) => println!("{}", result.pretty_print()),
+        Err(machine::UnsupportedTerm(term)) => {
+          println!("The Krivine machine does not support {:?} yet", term)
+        }
+      },
+      Err(err) => report_parse_error(expr, &err),
+    }
+    return;
+  }
+
+  if let Some(path) = line.strip_prefix(":load ") {
+    match session.load(Path::new(path.trim())) {
+      Ok(loaded) => println!("Loaded {} declaration(s)", loaded),
+      Err(err) => println!("{}", err),
     }
+    return;
+  }
 
-    #[test]
-    fn test_interpret_identity_application() {
-      let path = Path::new("/Users/alanbertani/dev/DepenLang/test_files/capture.lisp");
-      let result = interpret_file(path).unwrap();
-      assert_eq!(result, Term::Var("z".to_string())); // Applying identity function to z
+  if line == ":env" {
+    let mut names: Vec<&String> = session.env.keys().collect();
+    names.sort();
+    for name in names {
+      println!("{} = {}", name, reify(session.env[name].clone()).pretty_print());
     }
+    return;
+  }
+
+  if line == ":clear" {
+    session.env.clear();
+    return;
+  }
 
-    #[test]
-    fn test_interpret_nested_application() {
-      let path = Path::new("/Users/alanbertani/dev/DepenLang/test_files/nested.lisp");
-      let result = interpret_file(path).unwrap();
-        assert_eq!(result, Term::Var("a".to_string())); // Applying nested function, returns "a"
+  if let Some((name, body)) = parse_let(line) {
+    match session.eval_source(body) {
+      Ok(value) => {
+        println!("{} = {}", name, reify(value.clone()).pretty_print());
+        session.env.insert(name.to_string(), value);
+      }
+      Err(err) => report_parse_error(body, &err),
     }
+    return;
+  }
+
+  match session.eval_source(line) {
+    Ok(value) => println!("{}", reify(value).pretty_print()),
+    Err(err) => report_parse_error(line, &err),
+  }
+}
+
+// A line-oriented REPL: each input is lexed and parsed with the real
+// front end, then normalized against a persistent `Session` of `let`
+// bindings and `:load`ed function declarations.
+fn run_repl() {
+  let stdin = io::stdin();
+  let mut session = Session::new();
+
+  print!("> ");
+  io::stdout().flush().ok();
+  for line in stdin.lock().lines() {
+    let Ok(line) = line else { break };
+    let line = line.trim();
+    if !line.is_empty() {
+      handle_line(line, &mut session);
+    }
+    print!("> ");
+    io::stdout().flush().ok();
+  }
+}
+
+fn main() -> io::Result<()> {
+  let args: Vec<String> = env::args().collect();
+
+  if args.len() > 1 {
+    for test_file in &args[1..] {
+      let path = Path::new(test_file);
+      match interpret_file(path) {
+        Ok(term) => println!("Interpreted: {}", term.pretty_print()),
+        Err(e) => eprintln!("Error interpreting file {}: {}", test_file, e),
+      }
+    }
+  } else {
+    run_repl();
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_interpret_identity() {
+    let path = Path::new("test_files/identity.lisp");
+    let result = interpret_file(path).unwrap();
+    assert_eq!(result, Term::Abs("x".to_string(), Box::new(Term::Var("x".to_string()))));
+  }
+
+  #[test]
+  fn test_interpret_constant() {
+    // Reification probes each binder with a fresh name rather than keeping
+    // the source's own parameter names, so `\x. \y. x` comes back as
+    // `\x. \x'. x` (see `interpreter::reify`).
+    let path = Path::new("test_files/constant.lisp");
+    let result = interpret_file(path).unwrap();
+    assert_eq!(
+      result,
+      Term::Abs("x".to_string(), Box::new(Term::Abs("x'".to_string(), Box::new(Term::Var("x".to_string())))))
+    );
+  }
+
+  #[test]
+  fn test_interpret_identity_application() {
+    let path = Path::new("test_files/capture.lisp");
+    let result = interpret_file(path).unwrap();
+    assert_eq!(result, Term::Var("z".to_string())); // Applying identity function to z
+  }
+
+  #[test]
+  fn test_interpret_nested_application() {
+    let path = Path::new("test_files/nested.lisp");
+    let result = interpret_file(path).unwrap();
+    assert_eq!(result, Term::Var("a".to_string())); // Applying nested function, returns "a"
+  }
+
+  #[test]
+  fn test_interpret_file_with_let_bindings() {
+    let path = Path::new("test_files/let_bindings.lisp");
+    let result = interpret_file(path).unwrap();
+    assert_eq!(result, Term::Var("a".to_string()));
+  }
+
+  #[test]
+  fn test_session_load_brings_fun_decls_into_scope() {
+    // Once `Add`'s declaration is loaded, the session's parser resolves
+    // `Add (S Z) (S Z)` to a `Term::Fun` call, and `eval` has the rules to
+    // actually reduce it: 1 + 1 = 2.
+    let mut session = Session::new();
+    // `nat.decl` has two declarations: the `Nat`/`Z`/`S` data declaration
+    // and the `Add` function declaration.
+    let loaded = session.load(Path::new("test_files/nat.decl")).unwrap();
+    assert_eq!(loaded, 2);
+
+    let result = reify(session.eval_source("Add (S Z) (S Z)").unwrap());
+    assert_eq!(
+      result,
+      Term::Ctr {
+        name: "S".to_string(),
+        args: vec![Term::Ctr {
+          name: "S".to_string(),
+          args: vec![Term::Ctr { name: "Z".to_string(), args: vec![] }],
+        }],
+      }
+    );
+  }
+
+  #[test]
+  fn test_session_load_brings_data_and_fun_signatures_into_ctx() {
+    // `:load`ing `nat.decl` should populate `ctx` with the data type itself
+    // (`Nat : Type`), each constructor's signature (`Z : Nat`,
+    // `S : Nat -> Nat`), and the function's signature (`Add`), so
+    // `typecheck::infer` can see all of them without a fresh `Ctx::new()`.
+    let mut session = Session::new();
+    session.load(Path::new("test_files/nat.decl")).unwrap();
+
+    let z = session.parse("Z").unwrap();
+    assert_eq!(typecheck::infer(&session.ctx, &z), Ok(Term::Var("Nat".to_string())));
+
+    let one = session.parse("S Z").unwrap();
+    assert_eq!(typecheck::infer(&session.ctx, &one), Ok(Term::Var("Nat".to_string())));
+
+    let sum = session.parse("Add (S Z) (S Z)").unwrap();
+    assert_eq!(typecheck::infer(&session.ctx, &sum), Ok(Term::Var("Nat".to_string())));
+  }
 }
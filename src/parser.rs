@@ -1,26 +1,51 @@
-use crate::lexer::{Token, Lexer};
-use crate::ast::Term;
+use std::collections::HashSet;
 
-#[derive(Debug, PartialEq)]
-pub enum ParseError {
+use crate::ast::{Op, Term};
+use crate::decl::{Constructor, DataDecl, Decl, FunDecl, Pattern, Rule};
+use crate::lexer::{Lexer, Span, Token};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
   UnexpectedToken(Token),
   UnexpectedEndOfInput,
   InvalidExpression,
+  // A lexer failure, carried through with its own message so the original
+  // diagnostic isn't lost once it reaches the parser layer.
+  LexError(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+  pub kind: ParseErrorKind,
+  pub span: Span,
 }
 
 pub struct Parser {
-  tokens: Vec<Token>,
+  tokens: Vec<(Token, Span)>,
   current: usize,
+  // Names already known to be user-defined functions (as opposed to data
+  // constructors), so an applied capitalized identifier like `Add a b` is
+  // parsed as `Term::Fun` rather than defaulting to `Term::Ctr`.
+  funs: HashSet<String>,
 }
 
 impl Parser {
-  pub fn new(input: &str) -> Self {
+  // Tells the parser which names are already known to be user-defined
+  // functions, so e.g. `Add 1 2` parses as `Term::Fun` rather than
+  // defaulting to `Term::Ctr` for a caller that already loaded `Add`'s
+  // declaration (via `parse_program`). A caller with no such names yet can
+  // just pass `HashSet::new()`.
+  pub fn with_funs(input: &str, funs: HashSet<String>) -> Result<Self, ParseError> {
     let mut lexer = Lexer::new(input);
-    let tokens = lexer.tokenize().unwrap_or_else(|e| panic!("Lexer error: {:?}", e));
-    Parser {
-      tokens,
-      current: 0,
-    }
+    let tokens = lexer.tokenize().map_err(|e| ParseError {
+      kind: ParseErrorKind::LexError(e.message),
+      span: e.span,
+    })?;
+    Ok(Parser { tokens, current: 0, funs })
+  }
+
+  fn from_tokens(tokens: Vec<(Token, Span)>, funs: HashSet<String>) -> Self {
+    Parser { tokens, current: 0, funs }
   }
 
   pub fn parse(&mut self) -> Result<Term, ParseError> {
@@ -28,58 +53,357 @@ impl Parser {
   }
 
   fn expression(&mut self) -> Result<Term, ParseError> {
-    self.application()
+    let mut term = self.additive()?;
+
+    if matches!(self.peek(), Some(Token::Colon)) {
+      self.advance();
+      let typ = self.expression()?;
+      term = Term::Ann(Box::new(term), Box::new(typ));
+    }
+
+    if matches!(self.peek(), Some(Token::Arrow)) {
+      self.advance();
+      let codomain = self.expression()?;
+      term = Term::Pi {
+        name: "_".to_string(),
+        domain: Box::new(term),
+        codomain: Box::new(codomain),
+      };
+    }
+
+    Ok(term)
+  }
+
+  // `+`/`-` are left-associative and bind more loosely than `*`.
+  fn additive(&mut self) -> Result<Term, ParseError> {
+    let mut term = self.multiplicative()?;
+    loop {
+      let op = match self.peek() {
+        Some(Token::Plus) => Op::Add,
+        Some(Token::Minus) => Op::Sub,
+        _ => break,
+      };
+      self.advance();
+      let right = self.multiplicative()?;
+      term = Term::Prim { op, left: Box::new(term), right: Box::new(right) };
+    }
+    Ok(term)
+  }
+
+  // `*` binds tighter than `+`/`-` but more loosely than application, so
+  // `f x * 2` parses as `(f x) * 2`.
+  fn multiplicative(&mut self) -> Result<Term, ParseError> {
+    let mut term = self.application()?;
+    while matches!(self.peek(), Some(Token::Star)) {
+      self.advance();
+      let right = self.application()?;
+      term = Term::Prim { op: Op::Mul, left: Box::new(term), right: Box::new(right) };
+    }
+    Ok(term)
   }
 
   fn application(&mut self) -> Result<Term, ParseError> {
-    let mut expr = self.atom()?;
-    while self.peek().is_some() && !matches!(self.peek(), Some(Token::RightParen)) {
-      let right = self.atom()?;
-      expr = Term::App(Box::new(expr), Box::new(right));
+    let head = self.atom()?;
+    let mut args = Vec::new();
+    while !matches!(
+      self.peek(),
+      None
+        | Some(Token::RightParen)
+        | Some(Token::Arrow)
+        | Some(Token::Colon)
+        | Some(Token::Plus)
+        | Some(Token::Minus)
+        | Some(Token::Star)
+    ) {
+      let arg = self.atom()?;
+      args.push(self.promote_bare_ctr(arg));
+    }
+
+    if args.is_empty() {
+      return Ok(head);
+    }
+
+    // A capitalized head applied to arguments is a data constructor (`S n`)
+    // or a user-defined function call (`Add a b`); anything else curries as
+    // plain lambda application, same as before.
+    match head {
+      Term::Var(name) if is_capitalized(&name) => {
+        if self.funs.contains(&name) {
+          Ok(Term::Fun { name, args })
+        } else {
+          Ok(Term::Ctr { name, args })
+        }
+      }
+      _ => Ok(
+        args
+          .into_iter()
+          .fold(head, |acc, arg| Term::App(Box::new(acc), Box::new(arg))),
+      ),
+    }
+  }
+
+  // An argument standing alone (no sub-arguments of its own) is still a
+  // reference to a nullary constructor if it's capitalized, e.g. the `Z` in
+  // `S Z`. A head with zero arguments is left as a bare `Var`, since that's
+  // also how a free type name like `Nat` parses in a signature.
+  fn promote_bare_ctr(&self, term: Term) -> Term {
+    match term {
+      Term::Var(name) if is_capitalized(&name) => {
+        if self.funs.contains(&name) {
+          Term::Fun { name, args: Vec::new() }
+        } else {
+          Term::Ctr { name, args: Vec::new() }
+        }
+      }
+      other => other,
     }
-    Ok(expr)
   }
 
   fn atom(&mut self) -> Result<Term, ParseError> {
-    match self.advance() {
-      Some(Token::Identifier(name)) => Ok(Term::Var(name)),
-      Some(Token::Lambda) => self.abstraction(),
-      Some(Token::LeftParen) => {
+    match self.advance_spanned() {
+      Some((Token::Identifier(name), _)) => Ok(Term::Var(name)),
+      Some((Token::Type, _)) => Ok(Term::Typ),
+      Some((Token::Number(n), _)) => Ok(Term::Lit(n)),
+      Some((Token::Lambda, _)) => self.abstraction(),
+      Some((Token::LeftParen, _)) => {
+        if matches!(self.peek(), Some(Token::Identifier(_)))
+          && matches!(self.peek_at(1), Some(Token::Colon))
+        {
+          return self.pi_type();
+        }
         let expr = self.expression()?;
         self.consume(Token::RightParen)?;
         Ok(expr)
       }
-      Some(token) => Err(ParseError::UnexpectedToken(token)),
-      None => Err(ParseError::UnexpectedEndOfInput),
+      Some((token, span)) => Err(ParseError { kind: ParseErrorKind::UnexpectedToken(token), span }),
+      None => Err(self.eof_error()),
     }
   }
 
+  // Parses the `(name : domain) -> codomain` form once the leading `(` has
+  // already been consumed and look-ahead has confirmed `name :` follows.
+  fn pi_type(&mut self) -> Result<Term, ParseError> {
+    let name = self.expect_identifier()?;
+    self.consume(Token::Colon)?;
+    let domain = self.expression()?;
+    self.consume(Token::RightParen)?;
+    self.consume(Token::Arrow)?;
+    let codomain = self.expression()?;
+    Ok(Term::Pi {
+      name,
+      domain: Box::new(domain),
+      codomain: Box::new(codomain),
+    })
+  }
+
   fn abstraction(&mut self) -> Result<Term, ParseError> {
-    let param = match self.advance() {
-      Some(Token::Identifier(name)) => name,
-      Some(token) => return Err(ParseError::UnexpectedToken(token)),
-      None => return Err(ParseError::UnexpectedEndOfInput),
-    };
+    let param = self.expect_identifier()?;
     self.consume(Token::Dot)?;
     let body = self.expression()?;
     Ok(Term::Abs(param, Box::new(body)))
   }
 
+  // Parses a typed parameter group `(name : type)`, as used by both
+  // constructor fields (`S (pred: Nat) : Nat`) and, in the future, explicit
+  // function parameter lists.
+  fn typed_param(&mut self) -> Result<(String, Term), ParseError> {
+    self.consume(Token::LeftParen)?;
+    let name = self.expect_identifier()?;
+    self.consume(Token::Colon)?;
+    let typ = self.expression()?;
+    self.consume(Token::RightParen)?;
+    Ok((name, typ))
+  }
+
+  // Parses one pattern, either a bare name (`b` binds, `Z` matches the
+  // nullary constructor) or a parenthesized constructor application
+  // (`(S b)`).
+  fn pattern_atom(&mut self) -> Result<Pattern, ParseError> {
+    match self.advance_spanned() {
+      Some((Token::Identifier(name), _)) => Ok(name_to_pattern(name, Vec::new())),
+      Some((Token::LeftParen, _)) => {
+        let pattern = self.pattern_application()?;
+        self.consume(Token::RightParen)?;
+        Ok(pattern)
+      }
+      Some((token, span)) => Err(ParseError { kind: ParseErrorKind::UnexpectedToken(token), span }),
+      None => Err(self.eof_error()),
+    }
+  }
+
+  // Parses a constructor pattern applied to its sub-patterns, e.g. the `S b`
+  // inside `(S b)`.
+  fn pattern_application(&mut self) -> Result<Pattern, ParseError> {
+    let name = self.expect_identifier()?;
+    let mut args = Vec::new();
+    while !matches!(self.peek(), None | Some(Token::RightParen) | Some(Token::Equals)) {
+      args.push(self.pattern_atom()?);
+    }
+    Ok(name_to_pattern(name, args))
+  }
+
+  // Parses one function-rule line (the head name has already been
+  // consumed), e.g. the `a (S b) = S (Add a b)` of `Add a (S b) = S (Add a b)`.
+  fn rule(&mut self) -> Result<Rule, ParseError> {
+    let mut patterns = Vec::new();
+    while !matches!(self.peek(), None | Some(Token::Equals)) {
+      patterns.push(self.pattern_atom()?);
+    }
+    self.consume(Token::Equals)?;
+    let body = self.expression()?;
+    Ok(Rule { patterns, body })
+  }
+
+  // Parses one line of a top-level program: a data/function signature
+  // header, a constructor line, or a function rule.
+  fn decl_line(
+    &mut self,
+    pending_data: &mut Option<String>,
+    pending_fun: &mut Option<String>,
+    decls: &mut Vec<Decl>,
+  ) -> Result<(), ParseError> {
+    let name = self.expect_identifier()?;
+
+    if matches!(self.peek(), Some(Token::Colon)) {
+      self.advance();
+      let typ = self.expression()?;
+
+      if typ == Term::Typ {
+        decls.push(Decl::Data(DataDecl { name: name.clone(), constructors: Vec::new() }));
+        *pending_data = Some(name);
+        *pending_fun = None;
+        return Ok(());
+      }
+
+      // A bare `Name : Type-name` right after a data header is a nullary
+      // constructor (`Z : Nat`), not a new signature; it has no parameters
+      // to record, but still belongs to the declaration in progress.
+      if let Some(data_name) = pending_data.clone() {
+        if typ == Term::Var(data_name.clone()) {
+          let decl = decls
+            .iter_mut()
+            .rev()
+            .find_map(|decl| match decl {
+              Decl::Data(data_decl) if data_decl.name == data_name => Some(data_decl),
+              _ => None,
+            })
+            .ok_or_else(|| self.invalid_expression())?;
+          decl.constructors.push(Constructor { name, args: Vec::new() });
+          return Ok(());
+        }
+      }
+
+      decls.push(Decl::Fun(FunDecl { name: name.clone(), signature: typ, rules: Vec::new() }));
+      *pending_fun = Some(name);
+      *pending_data = None;
+      return Ok(());
+    }
+
+    if self.remaining_has(Token::Equals) {
+      let rule = self.rule()?;
+      match pending_fun {
+        Some(fun_name) if *fun_name == name => {}
+        _ => return Err(self.invalid_expression()),
+      }
+      let decl = decls
+        .iter_mut()
+        .rev()
+        .find_map(|decl| match decl {
+          Decl::Fun(fun_decl) if fun_decl.name == name => Some(fun_decl),
+          _ => None,
+        })
+        .ok_or_else(|| self.invalid_expression())?;
+      decl.rules.push(rule);
+      return Ok(());
+    }
+
+    let data_name = match pending_data {
+      Some(data_name) => data_name.clone(),
+      None => return Err(self.invalid_expression()),
+    };
+    let mut args = Vec::new();
+    while matches!(self.peek(), Some(Token::LeftParen)) {
+      args.push(self.typed_param()?);
+    }
+    self.consume(Token::Colon)?;
+    let return_type = self.expression()?;
+    match &return_type {
+      Term::Var(ret_name) if *ret_name == data_name => {}
+      _ => return Err(self.invalid_expression()),
+    }
+    let decl = decls
+      .iter_mut()
+      .rev()
+      .find_map(|decl| match decl {
+        Decl::Data(data_decl) if data_decl.name == data_name => Some(data_decl),
+        _ => None,
+      })
+      .ok_or_else(|| self.invalid_expression())?;
+    decl.constructors.push(Constructor { name, args });
+    Ok(())
+  }
+
+  fn remaining_has(&self, token: Token) -> bool {
+    self.tokens[self.current..].iter().any(|(t, _)| *t == token)
+  }
+
+  // Parses the next token as a bare identifier, or fails with the span of
+  // whatever was actually found.
+  fn expect_identifier(&mut self) -> Result<String, ParseError> {
+    match self.advance_spanned() {
+      Some((Token::Identifier(name), _)) => Ok(name),
+      Some((token, span)) => Err(ParseError { kind: ParseErrorKind::UnexpectedToken(token), span }),
+      None => Err(self.eof_error()),
+    }
+  }
+
   fn advance(&mut self) -> Option<Token> {
+    self.advance_spanned().map(|(token, _)| token)
+  }
+
+  fn advance_spanned(&mut self) -> Option<(Token, Span)> {
     if self.is_at_end() {
       None
     } else {
-      let token = self.tokens[self.current].clone();
+      let pair = self.tokens[self.current].clone();
       self.current += 1;
-      Some(token)
+      Some(pair)
     }
   }
 
   fn peek(&self) -> Option<&Token> {
-    if self.is_at_end() {
-      None
-    } else {
-      Some(&self.tokens[self.current])
+    self.tokens.get(self.current).map(|(token, _)| token)
+  }
+
+  fn peek_at(&self, offset: usize) -> Option<&Token> {
+    self.tokens.get(self.current + offset).map(|(token, _)| token)
+  }
+
+  fn peek_span(&self) -> Option<Span> {
+    self.tokens.get(self.current).map(|(_, span)| *span)
+  }
+
+  // The position just past the last token, used to point an end-of-input
+  // error at the tail of the source rather than at offset zero.
+  fn eof_span(&self) -> Span {
+    self
+      .tokens
+      .last()
+      .map(|(_, span)| Span::new(span.end, span.end))
+      .unwrap_or_else(|| Span::new(0, 0))
+  }
+
+  fn eof_error(&self) -> ParseError {
+    ParseError {
+      kind: ParseErrorKind::UnexpectedEndOfInput,
+      span: self.eof_span(),
+    }
+  }
+
+  fn invalid_expression(&self) -> ParseError {
+    ParseError {
+      kind: ParseErrorKind::InvalidExpression,
+      span: self.peek_span().unwrap_or_else(|| self.eof_span()),
     }
   }
 
@@ -88,7 +412,10 @@ impl Parser {
       self.advance();
       Ok(())
     } else {
-      Err(ParseError::UnexpectedToken(self.peek().cloned().unwrap_or(Token::Identifier("EOF".to_string()))))
+      match self.advance_spanned() {
+        Some((token, span)) => Err(ParseError { kind: ParseErrorKind::UnexpectedToken(token), span }),
+        None => Err(self.eof_error()),
+      }
     }
   }
 
@@ -101,15 +428,82 @@ impl Parser {
   }
 }
 
-pub fn parse(input: &str) -> Result<Term, ParseError> {
-  let mut parser = Parser::new(input);
+// Parses a single expression, resolving a bare capitalized application
+// against an already-known set of function names (see `Parser::with_funs`).
+pub fn parse_with_funs(input: &str, funs: HashSet<String>) -> Result<Term, ParseError> {
+  let mut parser = Parser::with_funs(input, funs)?;
   parser.parse()
 }
 
+// The file-level program loader: parses a full source file of data and
+// function declarations (the `Nat : Type` / `Z : Nat` / `Add a (S b) = ...`
+// style surface) into an ordered list of `Decl`s.
+pub fn parse_program(source: &str) -> Result<Vec<Decl>, ParseError> {
+  let mut lexer = Lexer::new(source);
+  let tokens = lexer.tokenize().map_err(|e| ParseError {
+    kind: ParseErrorKind::LexError(e.message),
+    span: e.span,
+  })?;
+
+  let mut decls = Vec::new();
+  let mut pending_data = None;
+  let mut pending_fun = None;
+  let mut funs = HashSet::new();
+
+  for line in split_lines(&tokens) {
+    if line.is_empty() {
+      continue;
+    }
+    let mut parser = Parser::from_tokens(line, funs.clone());
+    parser.decl_line(&mut pending_data, &mut pending_fun, &mut decls)?;
+    if let Some(fun_name) = &pending_fun {
+      funs.insert(fun_name.clone());
+    }
+  }
+
+  Ok(decls)
+}
+
+// Splits a token stream into lines on `Token::Newline`, dropping the
+// indentation markers (not yet load-bearing for this grammar).
+fn split_lines(tokens: &[(Token, Span)]) -> Vec<Vec<(Token, Span)>> {
+  let mut lines = Vec::new();
+  let mut current = Vec::new();
+  for (token, span) in tokens {
+    match token {
+      Token::Newline => lines.push(std::mem::take(&mut current)),
+      Token::Indent(_) => {}
+      other => current.push((other.clone(), *span)),
+    }
+  }
+  if !current.is_empty() {
+    lines.push(current);
+  }
+  lines
+}
+
+fn is_capitalized(name: &str) -> bool {
+  name.chars().next().map_or(false, |c| c.is_uppercase())
+}
+
+fn name_to_pattern(name: String, args: Vec<Pattern>) -> Pattern {
+  if is_capitalized(&name) {
+    Pattern::Ctr(name, args)
+  } else {
+    Pattern::Var(name)
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  // Most tests below predate `Parser::with_funs`/`parse_with_funs` and
+  // don't care about resolving any capitalized calls to `Term::Fun`.
+  fn parse(input: &str) -> Result<Term, ParseError> {
+    parse_with_funs(input, HashSet::new())
+  }
+
   #[test]
   fn test_parse_variable() {
     assert_eq!(parse("x"), Ok(Term::Var("x".to_string())));
@@ -134,6 +528,46 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_parse_non_dependent_pi() {
+    assert_eq!(
+      parse("Nat -> Nat"),
+      Ok(Term::Pi {
+        name: "_".to_string(),
+        domain: Box::new(Term::Var("Nat".to_string())),
+        codomain: Box::new(Term::Var("Nat".to_string())),
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_dependent_pi() {
+    assert_eq!(
+      parse("(a: Type) -> a"),
+      Ok(Term::Pi {
+        name: "a".to_string(),
+        domain: Box::new(Term::Typ),
+        codomain: Box::new(Term::Var("a".to_string())),
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_annotation() {
+    // The lambda body extends as far right as possible, so the annotation
+    // attaches to `x`, not to the whole abstraction.
+    assert_eq!(
+      parse("\\x. x : Nat"),
+      Ok(Term::Abs(
+        "x".to_string(),
+        Box::new(Term::Ann(
+          Box::new(Term::Var("x".to_string())),
+          Box::new(Term::Var("Nat".to_string())),
+        ))
+      ))
+    );
+  }
+
   #[test]
   fn test_parse_complex_term() {
     assert_eq!(
@@ -153,5 +587,180 @@ mod tests {
       ))
     );
   }
-  
-}
\ No newline at end of file
+
+  #[test]
+  fn test_parse_constructor_application() {
+    assert_eq!(
+      parse("S (S Z)"),
+      Ok(Term::Ctr {
+        name: "S".to_string(),
+        args: vec![Term::Ctr {
+          name: "S".to_string(),
+          args: vec![Term::Ctr { name: "Z".to_string(), args: vec![] }],
+        }],
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_nat_data_decl() {
+    let source = "Nat : Type\nZ : Nat\nS (pred: Nat) : Nat";
+    let decls = parse_program(source).unwrap();
+
+    assert_eq!(
+      decls,
+      vec![Decl::Data(DataDecl {
+        name: "Nat".to_string(),
+        constructors: vec![
+          Constructor { name: "Z".to_string(), args: vec![] },
+          Constructor {
+            name: "S".to_string(),
+            args: vec![("pred".to_string(), Term::Var("Nat".to_string()))],
+          },
+        ],
+      })]
+    );
+  }
+
+  #[test]
+  fn test_parse_add_function_rules() {
+    let source = "Add : Nat -> Nat -> Nat\nAdd a Z = a\nAdd a (S b) = S (Add a b)";
+    let decls = parse_program(source).unwrap();
+
+    assert_eq!(
+      decls,
+      vec![Decl::Fun(FunDecl {
+        name: "Add".to_string(),
+        signature: Term::Pi {
+          name: "_".to_string(),
+          domain: Box::new(Term::Var("Nat".to_string())),
+          codomain: Box::new(Term::Pi {
+            name: "_".to_string(),
+            domain: Box::new(Term::Var("Nat".to_string())),
+            codomain: Box::new(Term::Var("Nat".to_string())),
+          }),
+        },
+        rules: vec![
+          Rule {
+            patterns: vec![Pattern::Var("a".to_string()), Pattern::Ctr("Z".to_string(), vec![])],
+            body: Term::Var("a".to_string()),
+          },
+          Rule {
+            patterns: vec![
+              Pattern::Var("a".to_string()),
+              Pattern::Ctr("S".to_string(), vec![Pattern::Var("b".to_string())]),
+            ],
+            body: Term::Ctr {
+              name: "S".to_string(),
+              args: vec![Term::Fun {
+                name: "Add".to_string(),
+                args: vec![Term::Var("a".to_string()), Term::Var("b".to_string())],
+              }],
+            },
+          },
+        ],
+      })]
+    );
+  }
+
+  #[test]
+  fn test_parse_numeric_literal() {
+    assert_eq!(parse("42"), Ok(Term::Lit(42)));
+  }
+
+  #[test]
+  fn test_parse_arithmetic_precedence() {
+    // `*` binds tighter than `+`, so this is `1 + (2 * 3)`.
+    assert_eq!(
+      parse("1 + 2 * 3"),
+      Ok(Term::Prim {
+        op: Op::Add,
+        left: Box::new(Term::Lit(1)),
+        right: Box::new(Term::Prim {
+          op: Op::Mul,
+          left: Box::new(Term::Lit(2)),
+          right: Box::new(Term::Lit(3)),
+        }),
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_arithmetic_left_associative() {
+    // `-` and `+` are left-associative, so this is `(1 - 2) + 3`.
+    assert_eq!(
+      parse("1 - 2 + 3"),
+      Ok(Term::Prim {
+        op: Op::Add,
+        left: Box::new(Term::Prim {
+          op: Op::Sub,
+          left: Box::new(Term::Lit(1)),
+          right: Box::new(Term::Lit(2)),
+        }),
+        right: Box::new(Term::Lit(3)),
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_arithmetic_with_application() {
+    // Application binds tighter than any operator, so this is `(f x) + 1`.
+    assert_eq!(
+      parse("f x + 1"),
+      Ok(Term::Prim {
+        op: Op::Add,
+        left: Box::new(Term::App(
+          Box::new(Term::Var("f".to_string())),
+          Box::new(Term::Var("x".to_string())),
+        )),
+        right: Box::new(Term::Lit(1)),
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_error_has_span() {
+    // "\x y" is missing the `.` that should follow the bound variable; the
+    // error should point at the `y` that was found instead.
+    let err = parse("\\x y").unwrap_err();
+    assert_eq!(
+      err,
+      ParseError {
+        kind: ParseErrorKind::UnexpectedToken(Token::Identifier("y".to_string())),
+        span: Span::new(3, 4),
+      }
+    );
+  }
+
+  #[test]
+  fn test_lexer_error_propagates_through_parser() {
+    match Parser::with_funs("x -> @", HashSet::new()) {
+      Err(err) => {
+        assert_eq!(err.kind, ParseErrorKind::LexError("Unexpected character: @".to_string()));
+        assert_eq!(err.span, Span::new(5, 6));
+      }
+      Ok(_) => panic!("expected a lex error"),
+    }
+  }
+
+  #[test]
+  fn test_parse_defaults_capitalized_call_to_ctr() {
+    // With no `funs` supplied, a capitalized application is a constructor.
+    assert_eq!(
+      parse("Add 1 2"),
+      Ok(Term::Ctr { name: "Add".to_string(), args: vec![Term::Lit(1), Term::Lit(2)] })
+    );
+  }
+
+  #[test]
+  fn test_parse_with_funs_resolves_known_name_to_fun_call() {
+    // Telling the parser `Add` is a known function (as `parse_program` would
+    // after loading its declaration) makes the same call a `Term::Fun`.
+    let mut funs = HashSet::new();
+    funs.insert("Add".to_string());
+    assert_eq!(
+      parse_with_funs("Add 1 2", funs),
+      Ok(Term::Fun { name: "Add".to_string(), args: vec![Term::Lit(1), Term::Lit(2)] })
+    );
+  }
+}
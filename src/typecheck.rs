@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use crate::ast::{subst, Term};
+use crate::interpreter::{eval, reify, Env, Rules};
+
+// Maps variable names to their types (as opposed to `interpreter::Env`,
+// which maps them to values).
+pub type Ctx = HashMap<String, Term>;
+
+#[derive(Debug, PartialEq)]
+pub enum TypeError {
+  UnknownVariable(String),
+  NotAFunctionType(Term),
+  TypeMismatch { expected: Term, found: Term },
+  CannotInferLambda(Term),
+}
+
+// Reduces a type to its normal form so that definitional equality can be
+// decided by comparing alpha-equivalent terms structurally.
+fn normalize(term: &Term) -> Term {
+  reify(eval(term.clone(), &Env::new(), &Rules::new()))
+}
+
+fn types_equal(a: &Term, b: &Term) -> bool {
+  normalize(a) == normalize(b)
+}
+
+// Infers the type of `term` under `ctx`.
+pub fn infer(ctx: &Ctx, term: &Term) -> Result<Term, TypeError> {
+  match term {
+    Term::Var(x) => ctx
+      .get(x)
+      .cloned()
+      .ok_or_else(|| TypeError::UnknownVariable(x.clone())),
+
+    Term::Typ => Ok(Term::Typ),
+
+    Term::Pi { name, domain, codomain } => {
+      check(ctx, domain, &Term::Typ)?;
+      let mut inner_ctx = ctx.clone();
+      inner_ctx.insert(name.clone(), (**domain).clone());
+      check(&inner_ctx, codomain, &Term::Typ)?;
+      Ok(Term::Typ)
+    }
+
+    Term::Ann(term, typ) => {
+      check(ctx, typ, &Term::Typ)?;
+      check(ctx, term, typ)?;
+      Ok((**typ).clone())
+    }
+
+    Term::App(func, arg) => {
+      let func_type = infer(ctx, func)?;
+      match normalize(&func_type) {
+        Term::Pi { name, domain, codomain } => {
+          check(ctx, arg, &domain)?;
+          Ok(subst(&name, arg, &codomain))
+        }
+        other => Err(TypeError::NotAFunctionType(other)),
+      }
+    }
+
+    Term::Abs(_, _) => Err(TypeError::CannotInferLambda(term.clone())),
+
+    // Constructors and function calls are typed like a chain of `App`s
+    // against the signature recorded for `name` in `ctx`.
+    Term::Ctr { name, args } | Term::Fun { name, args } => {
+      let mut result_type = ctx
+        .get(name)
+        .cloned()
+        .ok_or_else(|| TypeError::UnknownVariable(name.clone()))?;
+      for arg in args {
+        match normalize(&result_type) {
+          Term::Pi { name: param, domain, codomain } => {
+            check(ctx, arg, &domain)?;
+            result_type = subst(&param, arg, &codomain);
+          }
+          other => return Err(TypeError::NotAFunctionType(other)),
+        }
+      }
+      Ok(result_type)
+    }
+
+    // Native numbers and the primitive operators over them share a single
+    // builtin type, the same way Kind2's `U60` isn't declared as a `Data`.
+    Term::Lit(_) => Ok(Term::Var("U60".to_string())),
+
+    Term::Prim { left, right, .. } => {
+      let u60 = Term::Var("U60".to_string());
+      check(ctx, left, &u60)?;
+      check(ctx, right, &u60)?;
+      Ok(u60)
+    }
+  }
+}
+
+// Checks that `term` has type `expected` under `ctx`.
+pub fn check(ctx: &Ctx, term: &Term, expected: &Term) -> Result<(), TypeError> {
+  match term {
+    Term::Abs(param, body) => match normalize(expected) {
+      Term::Pi { name, domain, codomain } => {
+        let mut inner_ctx = ctx.clone();
+        inner_ctx.insert(param.clone(), *domain);
+        let codomain = subst(&name, &Term::Var(param.clone()), &codomain);
+        check(&inner_ctx, body, &codomain)
+      }
+      other => Err(TypeError::NotAFunctionType(other)),
+    },
+    _ => {
+      let found = infer(ctx, term)?;
+      if types_equal(&found, expected) {
+        Ok(())
+      } else {
+        Err(TypeError::TypeMismatch { expected: expected.clone(), found })
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_infer_identity_type() {
+    // \a. \x. x : (a : Type) -> a -> a, the polymorphic identity function.
+    let identity = Term::Abs(
+      "a".to_string(),
+      Box::new(Term::Abs("x".to_string(), Box::new(Term::Var("x".to_string())))),
+    );
+    let identity_type = Term::Pi {
+      name: "a".to_string(),
+      domain: Box::new(Term::Typ),
+      codomain: Box::new(Term::Pi {
+        name: "_".to_string(),
+        domain: Box::new(Term::Var("a".to_string())),
+        codomain: Box::new(Term::Var("a".to_string())),
+      }),
+    };
+
+    let ctx = Ctx::new();
+    assert_eq!(check(&ctx, &identity, &identity_type), Ok(()));
+  }
+
+  #[test]
+  fn test_infer_application() {
+    let mut ctx = Ctx::new();
+    ctx.insert(
+      "f".to_string(),
+      Term::Pi {
+        name: "_".to_string(),
+        domain: Box::new(Term::Var("Nat".to_string())),
+        codomain: Box::new(Term::Var("Nat".to_string())),
+      },
+    );
+    ctx.insert("n".to_string(), Term::Var("Nat".to_string()));
+
+    let app = Term::App(Box::new(Term::Var("f".to_string())), Box::new(Term::Var("n".to_string())));
+    assert_eq!(infer(&ctx, &app), Ok(Term::Var("Nat".to_string())));
+  }
+
+  #[test]
+  fn test_unknown_variable() {
+    let ctx = Ctx::new();
+    let term = Term::Var("missing".to_string());
+    assert_eq!(infer(&ctx, &term), Err(TypeError::UnknownVariable("missing".to_string())));
+  }
+
+  #[test]
+  fn test_type_mismatch() {
+    let mut ctx = Ctx::new();
+    ctx.insert("n".to_string(), Term::Var("Nat".to_string()));
+
+    let term = Term::Var("n".to_string());
+    let result = check(&ctx, &term, &Term::Var("Bool".to_string()));
+
+    assert_eq!(
+      result,
+      Err(TypeError::TypeMismatch {
+        expected: Term::Var("Bool".to_string()),
+        found: Term::Var("Nat".to_string()),
+      })
+    );
+  }
+
+  #[test]
+  fn test_infer_prim_arithmetic() {
+    use crate::ast::Op;
+
+    let ctx = Ctx::new();
+    let term = Term::Prim {
+      op: Op::Add,
+      left: Box::new(Term::Lit(1)),
+      right: Box::new(Term::Lit(2)),
+    };
+    assert_eq!(infer(&ctx, &term), Ok(Term::Var("U60".to_string())));
+  }
+
+  #[test]
+  fn test_cannot_infer_bare_lambda() {
+    let ctx = Ctx::new();
+    let identity = Term::Abs("x".to_string(), Box::new(Term::Var("x".to_string())));
+    assert_eq!(infer(&ctx, &identity), Err(TypeError::CannotInferLambda(identity)));
+  }
+}